@@ -1,23 +1,324 @@
+use std::sync::OnceLock;
 use tauri::Manager;
+use tokio::sync::Mutex as AsyncMutex;
+
+/// Serializa todo acceso que mute el clipboard del sistema: tanto el
+/// copy/restore de `get_selection_text` como el copy/restore diferido de
+/// `copy_and_paste` (que sigue corriendo en un task separado mientras la
+/// función ya retornó). Sin este lock compartido, un `get_selection_text`
+/// que cae al fallback de copiar puede pisar o perder la restauración
+/// pendiente de un `copy_and_paste` previo. Es un `tokio::sync::Mutex`
+/// porque el guard tiene que poder cruzar un `.await` (se mueve dentro del
+/// task de restauración diferida).
+static CLIPBOARD_MUTATION_LOCK: AsyncMutex<()> = AsyncMutex::const_new(());
+
+/// Qué selección del sistema se quiere leer/escribir. `Primary` solo existe
+/// en X11/Wayland (el texto que el usuario resalta con el mouse); en otras
+/// plataformas un provider puede simplemente no soportarla.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum SelectionKind {
+    Clipboard,
+    Primary,
+}
+
+/// Backend capaz de leer/escribir el clipboard del sistema. `arboard` cubre
+/// el caso común, pero en Linux headless o Wayland puede fallar al
+/// inicializarse, así que se permite caer a binarios externos.
+trait ClipboardProvider: Send + Sync {
+    fn name(&self) -> &'static str;
+    fn get_contents(&self, kind: SelectionKind) -> Result<String, String>;
+    fn set_contents(&self, kind: SelectionKind, text: &str) -> Result<(), String>;
+}
+
+struct ArboardProvider;
+
+impl ClipboardProvider for ArboardProvider {
+    fn name(&self) -> &'static str {
+        "arboard"
+    }
+
+    fn get_contents(&self, kind: SelectionKind) -> Result<String, String> {
+        if kind == SelectionKind::Primary {
+            return Err("primary selection not supported by arboard".to_string());
+        }
+
+        arboard::Clipboard::new()
+            .map_err(|e| e.to_string())?
+            .get_text()
+            .map_err(|e| e.to_string())
+    }
+
+    fn set_contents(&self, kind: SelectionKind, text: &str) -> Result<(), String> {
+        if kind == SelectionKind::Primary {
+            return Err("primary selection not supported by arboard".to_string());
+        }
+
+        arboard::Clipboard::new()
+            .map_err(|e| e.to_string())?
+            .set_text(text)
+            .map_err(|e| e.to_string())
+    }
+}
+
+/// Provider que delega en un binario externo (`wl-copy`/`wl-paste`, `xclip`,
+/// `xsel`), pasando el texto por stdin/stdout. Tiene un comando separado por
+/// `SelectionKind` porque cada herramienta expone la selección primaria con
+/// una flag distinta.
+#[cfg(target_os = "linux")]
+struct CommandProvider {
+    name: &'static str,
+    clipboard_get_cmd: &'static [&'static str],
+    clipboard_set_cmd: &'static [&'static str],
+    primary_get_cmd: &'static [&'static str],
+    primary_set_cmd: &'static [&'static str],
+}
+
+#[cfg(target_os = "linux")]
+impl CommandProvider {
+    fn cmds_for(&self, kind: SelectionKind) -> (&'static [&'static str], &'static [&'static str]) {
+        match kind {
+            SelectionKind::Clipboard => (self.clipboard_get_cmd, self.clipboard_set_cmd),
+            SelectionKind::Primary => (self.primary_get_cmd, self.primary_set_cmd),
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl ClipboardProvider for CommandProvider {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn get_contents(&self, kind: SelectionKind) -> Result<String, String> {
+        let (get_cmd, _) = self.cmds_for(kind);
+
+        let output = std::process::Command::new(get_cmd[0])
+            .args(&get_cmd[1..])
+            .output()
+            .map_err(|e| e.to_string())?;
+
+        if !output.status.success() {
+            return Err(format!("{} exited with {}", self.name, output.status));
+        }
+
+        String::from_utf8(output.stdout).map_err(|e| e.to_string())
+    }
+
+    fn set_contents(&self, kind: SelectionKind, text: &str) -> Result<(), String> {
+        use std::io::Write;
+
+        let (_, set_cmd) = self.cmds_for(kind);
+
+        let mut child = std::process::Command::new(set_cmd[0])
+            .args(&set_cmd[1..])
+            .stdin(std::process::Stdio::piped())
+            .spawn()
+            .map_err(|e| e.to_string())?;
+
+        child
+            .stdin
+            .take()
+            .ok_or_else(|| "failed to open stdin".to_string())?
+            .write_all(text.as_bytes())
+            .map_err(|e| e.to_string())?;
+
+        child.wait().map_err(|e| e.to_string())?;
+        Ok(())
+    }
+}
+
+/// Prueba si un binario se puede ejecutar, sin depender de que exista un
+/// `which` en el sistema (muchas imágenes headless/minimal no lo traen).
+/// Si el proceso logra lanzarse, el binario está disponible; no importa si
+/// la flag de versión no es exactamente la que espera (el único resultado
+/// que nos interesa es si `Command::new` pudo encontrar el ejecutable).
+#[cfg(target_os = "linux")]
+fn binary_available(bin: &str) -> bool {
+    std::process::Command::new(bin)
+        .arg("--version")
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .status()
+        .is_ok()
+}
+
+#[cfg(target_os = "linux")]
+fn is_wayland() -> bool {
+    std::env::var("WAYLAND_DISPLAY").is_ok()
+}
+
+/// Candidatos de binarios externos, en orden de preferencia según la
+/// sesión (Wayland vs. X11).
+#[cfg(target_os = "linux")]
+fn command_candidates() -> Vec<CommandProvider> {
+    if is_wayland() {
+        vec![
+            CommandProvider {
+                name: "wl-clipboard",
+                clipboard_get_cmd: &["wl-paste", "--no-newline"],
+                clipboard_set_cmd: &["wl-copy"],
+                primary_get_cmd: &["wl-paste", "--primary", "--no-newline"],
+                primary_set_cmd: &["wl-copy", "--primary"],
+            },
+            CommandProvider {
+                name: "xclip",
+                clipboard_get_cmd: &["xclip", "-selection", "clipboard", "-o"],
+                clipboard_set_cmd: &["xclip", "-selection", "clipboard"],
+                primary_get_cmd: &["xclip", "-selection", "primary", "-o"],
+                primary_set_cmd: &["xclip", "-selection", "primary"],
+            },
+            CommandProvider {
+                name: "xsel",
+                clipboard_get_cmd: &["xsel", "--clipboard", "--output"],
+                clipboard_set_cmd: &["xsel", "--clipboard", "--input"],
+                primary_get_cmd: &["xsel", "--primary", "--output"],
+                primary_set_cmd: &["xsel", "--primary", "--input"],
+            },
+        ]
+    } else {
+        vec![
+            CommandProvider {
+                name: "xclip",
+                clipboard_get_cmd: &["xclip", "-selection", "clipboard", "-o"],
+                clipboard_set_cmd: &["xclip", "-selection", "clipboard"],
+                primary_get_cmd: &["xclip", "-selection", "primary", "-o"],
+                primary_set_cmd: &["xclip", "-selection", "primary"],
+            },
+            CommandProvider {
+                name: "xsel",
+                clipboard_get_cmd: &["xsel", "--clipboard", "--output"],
+                clipboard_set_cmd: &["xsel", "--clipboard", "--input"],
+                primary_get_cmd: &["xsel", "--primary", "--output"],
+                primary_set_cmd: &["xsel", "--primary", "--input"],
+            },
+            CommandProvider {
+                name: "wl-clipboard",
+                clipboard_get_cmd: &["wl-paste", "--no-newline"],
+                clipboard_set_cmd: &["wl-copy"],
+                primary_get_cmd: &["wl-paste", "--primary", "--no-newline"],
+                primary_set_cmd: &["wl-copy", "--primary"],
+            },
+        ]
+    }
+}
+
+/// Detecta qué backend usar: `arboard` si logra inicializarse, si no, en
+/// Linux, el primer binario externo disponible en orden de preferencia
+/// según la sesión (Wayland vs. X11).
+fn detect_provider() -> Box<dyn ClipboardProvider> {
+    if arboard::Clipboard::new().is_ok() {
+        let provider = ArboardProvider;
+        eprintln!("[clipboard] using provider: {}", provider.name());
+        return Box::new(provider);
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        for candidate in command_candidates() {
+            if binary_available(candidate.clipboard_get_cmd[0]) {
+                eprintln!("[clipboard] using provider: {}", candidate.name());
+                return Box::new(candidate);
+            }
+        }
+    }
+
+    eprintln!("[clipboard] no working clipboard backend found, defaulting to arboard");
+    Box::new(ArboardProvider)
+}
+
+static CLIPBOARD_PROVIDER: OnceLock<Box<dyn ClipboardProvider>> = OnceLock::new();
+
+fn provider() -> &'static dyn ClipboardProvider {
+    CLIPBOARD_PROVIDER.get_or_init(detect_provider).as_ref()
+}
+
+/// Qué herramienta externa puede leer/escribir la selección primaria de
+/// X11/Wayland. Se detecta de forma independiente del provider de
+/// `Clipboard`: `arboard` no soporta selección primaria en ninguna
+/// plataforma, así que aunque sea el provider activo para `Clipboard` (el
+/// caso común en una sesión de escritorio normal), igual hace falta probar
+/// los binarios externos para `Primary`.
+#[cfg(target_os = "linux")]
+static PRIMARY_SELECTION_PROVIDER: OnceLock<Option<CommandProvider>> = OnceLock::new();
+
+#[cfg(target_os = "linux")]
+fn primary_selection_provider() -> Option<&'static CommandProvider> {
+    PRIMARY_SELECTION_PROVIDER
+        .get_or_init(|| {
+            let found = command_candidates()
+                .into_iter()
+                .find(|candidate| binary_available(candidate.primary_get_cmd[0]));
+
+            match &found {
+                Some(candidate) => eprintln!(
+                    "[clipboard] using primary-selection provider: {}",
+                    candidate.name()
+                ),
+                None => eprintln!(
+                    "[clipboard] no primary-selection command tool found (xclip/xsel/wl-paste)"
+                ),
+            }
+
+            found
+        })
+        .as_ref()
+}
+
+/// Lee la selección pedida. Para `Primary` en Linux usa el provider de
+/// comandos externos detectado en forma independiente, en vez de depender
+/// de cuál sea el provider activo para `Clipboard`.
+#[cfg(target_os = "linux")]
+fn clipboard_get(kind: SelectionKind) -> Result<String, String> {
+    if kind == SelectionKind::Primary {
+        if let Some(primary) = primary_selection_provider() {
+            return primary.get_contents(SelectionKind::Primary);
+        }
+    }
+
+    provider().get_contents(kind)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn clipboard_get(kind: SelectionKind) -> Result<String, String> {
+    provider().get_contents(kind)
+}
+
+/// Ídem `clipboard_get` pero para escritura.
+#[cfg(target_os = "linux")]
+fn clipboard_set(kind: SelectionKind, text: &str) -> Result<(), String> {
+    if kind == SelectionKind::Primary {
+        if let Some(primary) = primary_selection_provider() {
+            return primary.set_contents(SelectionKind::Primary, text);
+        }
+    }
+
+    provider().set_contents(kind, text)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn clipboard_set(kind: SelectionKind, text: &str) -> Result<(), String> {
+    provider().set_contents(kind, text)
+}
 
 /// Copia texto al clipboard del sistema
 #[tauri::command]
 pub async fn copy_to_clipboard(text: String) -> Result<(), String> {
-    use arboard::Clipboard;
-    
-    let mut clipboard = Clipboard::new().map_err(|e| e.to_string())?;
-    clipboard.set_text(&text).map_err(|e| e.to_string())?;
-    
-    Ok(())
+    let _guard = CLIPBOARD_MUTATION_LOCK.lock().await;
+    clipboard_set(SelectionKind::Clipboard, &text)
 }
 
 /// Obtiene texto del clipboard
 #[tauri::command]
 pub async fn get_clipboard_text() -> Result<String, String> {
-    use arboard::Clipboard;
-    
-    let mut clipboard = Clipboard::new().map_err(|e| e.to_string())?;
-    clipboard.get_text().map_err(|e| e.to_string())
+    clipboard_get(SelectionKind::Clipboard)
+}
+
+/// Obtiene el texto de la selección primaria de X11/Wayland, es decir, el
+/// texto que el usuario tiene resaltado con el mouse sin haberlo copiado.
+/// Solo tiene efecto en Linux; en otras plataformas devuelve error.
+#[tauri::command]
+pub async fn get_primary_selection() -> Result<String, String> {
+    clipboard_get(SelectionKind::Primary)
 }
 
 /// Simula Ctrl+V (o Cmd+V en macOS) para pegar
@@ -47,39 +348,253 @@ pub async fn simulate_paste() -> Result<(), String> {
     Ok(())
 }
 
-/// Copia texto y lo pega automáticamente (todo en uno)
+/// Contenido previo del clipboard, capturado para poder restaurarlo luego
+/// de un `copy_and_paste`.
+enum ClipboardSnapshot {
+    Text(String),
+    Image(arboard::ImageData<'static>),
+    Empty,
+}
+
+/// Captura el clipboard actual a través del provider activo. Las imágenes
+/// solo `arboard` las expone, así que son un best-effort además del texto
+/// (los providers basados en comandos externos no las soportan).
+fn capture_clipboard_snapshot() -> ClipboardSnapshot {
+    if let Ok(previous_text) = provider().get_contents(SelectionKind::Clipboard) {
+        return ClipboardSnapshot::Text(previous_text);
+    }
+
+    if let Ok(mut clipboard) = arboard::Clipboard::new() {
+        if let Ok(previous_image) = clipboard.get_image() {
+            return ClipboardSnapshot::Image(previous_image.to_owned_img());
+        }
+    }
+
+    ClipboardSnapshot::Empty
+}
+
+/// Restaura un snapshot tomado por `capture_clipboard_snapshot`, vía el
+/// provider activo para texto (para degradar igual que el resto del
+/// subsistema en Linux headless/Wayland).
+fn restore_clipboard_snapshot(snapshot: ClipboardSnapshot) {
+    match snapshot {
+        ClipboardSnapshot::Text(previous) => {
+            let _ = provider().set_contents(SelectionKind::Clipboard, &previous);
+        }
+        ClipboardSnapshot::Image(previous) => {
+            if let Ok(mut clipboard) = arboard::Clipboard::new() {
+                let _ = clipboard.set_image(previous);
+            }
+        }
+        ClipboardSnapshot::Empty => {
+            let _ = provider().set_contents(SelectionKind::Clipboard, "");
+        }
+    }
+}
+
+/// Copia texto y lo pega automáticamente (todo en uno).
+///
+/// Por defecto preserva el clipboard del usuario: captura lo que había
+/// (texto o imagen) antes de copiar el texto dictado, y lo restaura una vez
+/// que la app destino tuvo tiempo de consumir el paste. Pasar
+/// `restore: Some(false)` deja el texto dictado en el clipboard.
 #[tauri::command]
-pub async fn copy_and_paste(text: String) -> Result<(), String> {
-    use arboard::Clipboard;
+pub async fn copy_and_paste(
+    text: String,
+    restore: Option<bool>,
+    restore_delay_ms: Option<u64>,
+) -> Result<(), String> {
     use enigo::{Enigo, Key, KeyboardControllable};
-    
-    // 1. Copiar al clipboard
-    let mut clipboard = Clipboard::new().map_err(|e| e.to_string())?;
-    clipboard.set_text(&text).map_err(|e| e.to_string())?;
-    
-    // 2. Pequeña pausa
+
+    let restore = restore.unwrap_or(true);
+    let restore_delay_ms = restore_delay_ms.unwrap_or(300);
+
+    // Mismo lock que usa el fallback de `get_selection_text`: se mantiene
+    // tomado hasta que la restauración diferida (paso 5) termine, para que
+    // ninguna otra mutación del clipboard se intercale en el medio.
+    let guard = CLIPBOARD_MUTATION_LOCK.lock().await;
+
+    // 1. Capturar lo que había en el clipboard antes de pisarlo
+    let snapshot = if restore {
+        capture_clipboard_snapshot()
+    } else {
+        ClipboardSnapshot::Empty
+    };
+
+    // 2. Copiar el texto dictado, a través del provider activo
+    provider().set_contents(SelectionKind::Clipboard, &text)?;
+
+    // 3. Pequeña pausa
     std::thread::sleep(std::time::Duration::from_millis(100));
-    
-    // 3. Simular paste
+
+    // 4. Simular paste
     let mut enigo = Enigo::new();
-    
+
     #[cfg(target_os = "macos")]
     {
         enigo.key_down(Key::Meta);
         enigo.key_click(Key::Layout('v'));
         enigo.key_up(Key::Meta);
     }
-    
+
     #[cfg(not(target_os = "macos"))]
     {
         enigo.key_down(Key::Control);
         enigo.key_click(Key::Layout('v'));
         enigo.key_up(Key::Control);
     }
-    
+
+    // 5. Restaurar el clipboard original, pero recién después de que la app
+    // destino tuvo tiempo de consumir el paste.
+    if restore {
+        tauri::async_runtime::spawn(async move {
+            tokio::time::sleep(std::time::Duration::from_millis(restore_delay_ms)).await;
+            restore_clipboard_snapshot(snapshot);
+            drop(guard);
+        });
+    } else {
+        drop(guard);
+    }
+
     Ok(())
 }
 
+/// Lee el texto seleccionado por el usuario en la app que tiene el foco.
+///
+/// Primero intenta leerlo vía accesibilidad (sin tocar el clipboard). Si la
+/// app no expone el texto seleccionado por ese medio, cae al método
+/// universal: simula Ctrl+C (Cmd+C en macOS) y lee el resultado del
+/// clipboard, restaurando el contenido original al terminar.
+#[tauri::command]
+pub async fn get_selection_text() -> Result<String, String> {
+    if let Some(text) = read_selection_via_accessibility() {
+        if !text.is_empty() {
+            return Ok(text);
+        }
+    }
+
+    read_selection_via_copy_fallback().await
+}
+
+/// Intenta leer el texto seleccionado usando las APIs de accesibilidad del
+/// sistema operativo, sin pasar por el clipboard. Devuelve `None` cuando la
+/// plataforma no tiene una implementación o la app enfocada no expone el
+/// atributo de selección.
+#[cfg(target_os = "macos")]
+fn read_selection_via_accessibility() -> Option<String> {
+    use accessibility_sys::{
+        kAXFocusedUIElementAttribute, kAXSelectedTextAttribute, AXUIElementCopyAttributeValue,
+        AXUIElementCreateSystemWide,
+    };
+    use core_foundation::base::{CFRelease, CFTypeRef, TCFType};
+    use core_foundation::string::{CFString, CFStringRef};
+
+    unsafe {
+        let system_wide = AXUIElementCreateSystemWide();
+        if system_wide.is_null() {
+            return None;
+        }
+
+        let focused_attr = CFString::new(kAXFocusedUIElementAttribute);
+        let mut focused_ref: CFTypeRef = std::ptr::null();
+        let result = AXUIElementCopyAttributeValue(
+            system_wide,
+            focused_attr.as_concrete_TypeRef(),
+            &mut focused_ref,
+        );
+        CFRelease(system_wide as CFTypeRef);
+        if result != 0 || focused_ref.is_null() {
+            return None;
+        }
+
+        let selected_attr = CFString::new(kAXSelectedTextAttribute);
+        let mut selected_ref: CFTypeRef = std::ptr::null();
+        let result = AXUIElementCopyAttributeValue(
+            focused_ref as *mut _,
+            selected_attr.as_concrete_TypeRef(),
+            &mut selected_ref,
+        );
+        CFRelease(focused_ref);
+        if result != 0 || selected_ref.is_null() {
+            return None;
+        }
+
+        let text = CFString::wrap_under_create_rule(selected_ref as CFStringRef).to_string();
+        Some(text)
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn read_selection_via_accessibility() -> Option<String> {
+    use uiautomation::UIAutomation;
+
+    let automation = UIAutomation::new().ok()?;
+    let focused = automation.get_focused_element().ok()?;
+    let pattern = focused.get_pattern::<uiautomation::patterns::UITextPattern>().ok()?;
+    let selection = pattern.get_selection().ok()?;
+    let range = selection.into_iter().next()?;
+    let text = range.get_text(-1).ok()?;
+
+    if text.is_empty() {
+        None
+    } else {
+        Some(text)
+    }
+}
+
+/// En Linux el texto resaltado con el mouse ya está disponible en la
+/// selección primaria, así que no hace falta simular un Ctrl+C.
+#[cfg(target_os = "linux")]
+fn read_selection_via_accessibility() -> Option<String> {
+    clipboard_get(SelectionKind::Primary).ok()
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
+fn read_selection_via_accessibility() -> Option<String> {
+    None
+}
+
+/// Fallback universal: simula un copy, lee el clipboard y restaura lo que
+/// había antes. Serializado con el mismo lock que `copy_and_paste` para que
+/// ninguna de las dos mutaciones del clipboard se pise con la otra.
+async fn read_selection_via_copy_fallback() -> Result<String, String> {
+    use enigo::{Enigo, Key, KeyboardControllable};
+
+    let _guard = CLIPBOARD_MUTATION_LOCK.lock().await;
+
+    let snapshot = capture_clipboard_snapshot();
+
+    let mut enigo = Enigo::new();
+
+    #[cfg(target_os = "macos")]
+    {
+        enigo.key_down(Key::Meta);
+        enigo.key_click(Key::Layout('c'));
+        enigo.key_up(Key::Meta);
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    {
+        enigo.key_down(Key::Control);
+        enigo.key_click(Key::Layout('c'));
+        enigo.key_up(Key::Control);
+    }
+
+    std::thread::sleep(std::time::Duration::from_millis(100));
+
+    let selected = provider()
+        .get_contents(SelectionKind::Clipboard)
+        .unwrap_or_default();
+
+    restore_clipboard_snapshot(snapshot);
+
+    if selected.is_empty() {
+        Err("no selection found via accessibility or clipboard fallback".to_string())
+    } else {
+        Ok(selected)
+    }
+}
+
 /// Escribe texto directamente (caracter por caracter) - alternativa a paste
 #[tauri::command]
 pub async fn type_text(text: String, delay_ms: Option<u64>) -> Result<(), String> {
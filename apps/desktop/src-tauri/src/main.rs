@@ -2,6 +2,7 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
 mod clipboard;
+mod shortcuts;
 
 use tauri::{
     menu::{Menu, MenuItem},
@@ -51,22 +52,10 @@ fn main() {
                 })
                 .build(app)?;
 
-            // Register global shortcut (Cmd/Ctrl + Shift + D)
+            // Registrar los atajos de teclado configurables (o los defaults
+            // si no hay config guardada todavía)
             #[cfg(desktop)]
-            {
-                use tauri_plugin_global_shortcut::{Code, GlobalShortcutExt, Modifiers, Shortcut};
-
-                let shortcut = Shortcut::new(Some(Modifiers::CONTROL | Modifiers::SUPER), Code::Space);
-                
-                app.global_shortcut().on_shortcut(shortcut, |app, _shortcut, _event| {
-                    if let Some(window) = app.get_webview_window("main") {
-                        let _ = window.show();
-                        let _ = window.set_focus();
-                        // Emit event to start dictation mode
-                        let _ = window.emit("start-dictation", ());
-                    }
-                })?;
-            }
+            shortcuts::register_all(app.handle())?;
 
             Ok(())
         })
@@ -76,6 +65,9 @@ fn main() {
             clipboard::simulate_paste,
             clipboard::copy_and_paste,
             clipboard::type_text,
+            clipboard::get_selection_text,
+            clipboard::get_primary_selection,
+            shortcuts::set_shortcut,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -0,0 +1,189 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut};
+
+/// Acciones de dictado que el usuario puede atar a un atajo de teclado.
+const ACTIONS: &[&str] = &[
+    "toggle_dictation",
+    "push_to_talk",
+    "rewrite_selection",
+    "show_window",
+];
+
+/// Atajos por defecto, usados cuando no hay config guardada o no se pudo
+/// parsear.
+fn default_shortcuts() -> HashMap<String, String> {
+    HashMap::from([
+        (
+            "toggle_dictation".to_string(),
+            "CommandOrControl+Shift+D".to_string(),
+        ),
+        (
+            "push_to_talk".to_string(),
+            "CommandOrControl+Shift+Space".to_string(),
+        ),
+        (
+            "rewrite_selection".to_string(),
+            "CommandOrControl+Shift+R".to_string(),
+        ),
+        (
+            "show_window".to_string(),
+            "CommandOrControl+Shift+A".to_string(),
+        ),
+    ])
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct ShortcutsConfig {
+    #[serde(default)]
+    shortcuts: HashMap<String, String>,
+}
+
+fn config_path(app: &AppHandle) -> Result<PathBuf, String> {
+    let dir = app.path().app_config_dir().map_err(|e| e.to_string())?;
+    Ok(dir.join("shortcuts.json"))
+}
+
+/// Lee la config persistida y la combina con los defaults (la config
+/// solo necesita especificar las acciones que el usuario quiere cambiar).
+/// Si el archivo no existe o no se puede parsear, cae a los defaults. Si el
+/// archivo parsea bien pero el acelerador de una acción puntual no es un
+/// `Shortcut` válido, esa acción puntual cae a su default en vez de dejar
+/// el resto de la config (que sí es válida) descartado.
+fn load_config(app: &AppHandle) -> HashMap<String, String> {
+    let mut shortcuts = default_shortcuts();
+
+    let path = match config_path(app) {
+        Ok(path) => path,
+        Err(_) => return shortcuts,
+    };
+
+    if let Ok(contents) = std::fs::read_to_string(&path) {
+        match serde_json::from_str::<ShortcutsConfig>(&contents) {
+            Ok(parsed) => {
+                for (action, accelerator) in parsed.shortcuts {
+                    if accelerator.parse::<Shortcut>().is_ok() {
+                        shortcuts.insert(action, accelerator);
+                    } else {
+                        eprintln!(
+                            "[shortcuts] invalid shortcut '{accelerator}' for {action}, using default"
+                        );
+                    }
+                }
+            }
+            Err(e) => {
+                eprintln!("[shortcuts] failed to parse {path:?}: {e}, using defaults");
+            }
+        }
+    }
+
+    shortcuts
+}
+
+fn save_config(app: &AppHandle, shortcuts: &HashMap<String, String>) -> Result<(), String> {
+    let path = config_path(app)?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+
+    let config = ShortcutsConfig {
+        shortcuts: shortcuts.clone(),
+    };
+    let contents = serde_json::to_string_pretty(&config).map_err(|e| e.to_string())?;
+    std::fs::write(path, contents).map_err(|e| e.to_string())
+}
+
+/// Evento emitido al webview cuando se dispara cada acción.
+fn event_name_for(action: &str) -> &'static str {
+    match action {
+        "toggle_dictation" => "start-dictation",
+        "push_to_talk" => "push-to-talk",
+        "rewrite_selection" => "rewrite-selection",
+        "show_window" => "show-window",
+        _ => "unknown-shortcut-action",
+    }
+}
+
+/// Estado compartido: qué acelerador está atado a cada acción ahora mismo,
+/// para poder desregistrar el anterior cuando `set_shortcut` lo cambia en
+/// caliente.
+struct ShortcutsState(Mutex<HashMap<String, String>>);
+
+/// Carga la config guardada (o los defaults) y registra un atajo global por
+/// cada acción conocida. Se llama una vez desde `setup`.
+pub fn register_all(app: &AppHandle) -> Result<(), String> {
+    let shortcuts = load_config(app);
+
+    for action in ACTIONS {
+        if let Some(accelerator) = shortcuts.get(*action) {
+            if let Err(e) = register_one(app, action, accelerator) {
+                eprintln!("[shortcuts] {e}");
+            }
+        }
+    }
+
+    app.manage(ShortcutsState(Mutex::new(shortcuts)));
+    Ok(())
+}
+
+fn register_one(app: &AppHandle, action: &str, accelerator: &str) -> Result<(), String> {
+    let shortcut: Shortcut = accelerator
+        .parse()
+        .map_err(|e| format!("invalid shortcut '{accelerator}' for {action}: {e}"))?;
+    let action = action.to_string();
+
+    app.global_shortcut()
+        .on_shortcut(shortcut, move |app, _shortcut, _event| {
+            if let Some(window) = app.get_webview_window("main") {
+                let _ = window.show();
+                let _ = window.set_focus();
+                let _ = window.emit(event_name_for(&action), ());
+            }
+        })
+        .map_err(|e| e.to_string())
+}
+
+/// Cambia en caliente el atajo de una acción: registra el nuevo, desregistra
+/// el anterior (si había uno válido) y persiste el cambio en disco.
+#[tauri::command]
+pub async fn set_shortcut(
+    app: AppHandle,
+    action: String,
+    accelerator: String,
+) -> Result<(), String> {
+    if !ACTIONS.contains(&action.as_str()) {
+        return Err(format!("unknown shortcut action: {action}"));
+    }
+
+    let state = app.state::<ShortcutsState>();
+    let previous = {
+        let shortcuts = state.0.lock().map_err(|e| e.to_string())?;
+        shortcuts.get(&action).cloned()
+    };
+
+    if previous.as_deref() == Some(accelerator.as_str()) {
+        // Mismo accelerator que ya estaba activo: nada que hacer.
+        return Ok(());
+    }
+
+    // Registrar el nuevo atajo antes de tocar el anterior: si `accelerator`
+    // no se puede parsear o la registración falla, el atajo viejo sigue
+    // activo y el estado/config no quedan desincronizados.
+    register_one(&app, &action, &accelerator)?;
+
+    if let Some(previous_accelerator) = previous {
+        if let Ok(previous_shortcut) = previous_accelerator.parse::<Shortcut>() {
+            let _ = app.global_shortcut().unregister(previous_shortcut);
+        }
+    }
+
+    let mut shortcuts = state.0.lock().map_err(|e| e.to_string())?;
+    shortcuts.insert(action, accelerator);
+    save_config(&app, &shortcuts)?;
+
+    Ok(())
+}